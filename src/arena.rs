@@ -0,0 +1,300 @@
+use std::alloc::Layout;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{drop_in_place, NonNull};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::provider::{ShMem, ShMemProvider};
+use crate::{PosixShMemProvider, ShmemConf};
+
+fn align_up(value: usize, align: usize) -> usize {
+    (value + align - 1) & !(align - 1)
+}
+
+/// Max number of disjoint free spans the header can track. Sized generously for typical
+/// alloc/free churn; a span that would overflow this on [`ArenaHeader::push_free`] is simply
+/// leaked (not corrupted) rather than tracked.
+const MAX_FREE_RANGES: usize = 64;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct FreeRange {
+    start: usize,
+    end: usize,
+}
+
+/// `header.ready` once the owner has finished writing the initial free list - see
+/// [`ShmemArena::new`].
+const HEADER_UNINIT: u32 = 0;
+const HEADER_READY: u32 = 1;
+
+/// Free-list metadata written at the very base of the arena's segment - not in any per-process
+/// Rust structure - so every process mapping the segment sees and mutates the *same* allocation
+/// state, regardless of where the mapping lands in its own address space. `ranges` stores offsets
+/// from the arena base rather than addresses for the same reason.
+///
+/// Guarded by `lock`, a simple cross-process spinlock, since more than one process may call
+/// `alloc`/`dealloc` concurrently. `ready` is a separate flag (not folded into `lock`) so a peer
+/// attaching before the owner has initialized the header can tell "not ready yet" apart from
+/// "ready, but momentarily locked".
+#[repr(C)]
+struct ArenaHeader {
+    ready: AtomicU32,
+    lock: AtomicU32,
+    len: usize,
+    ranges: [FreeRange; MAX_FREE_RANGES],
+}
+
+impl ArenaHeader {
+    /// # Safety
+    ///
+    /// `header` must point to at least `size_of::<ArenaHeader>()` bytes of writable memory that
+    /// outlive the guard, and no other live reference to `*header` may exist.
+    unsafe fn lock<'a>(header: *mut ArenaHeader) -> ArenaHeaderGuard<'a> {
+        let lock = &(*header).lock;
+        while lock
+            .compare_exchange_weak(0, 1, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            std::hint::spin_loop();
+        }
+        ArenaHeaderGuard {
+            header: &mut *header,
+        }
+    }
+
+    fn take_free(&mut self, layout: Layout) -> Option<FreeRange> {
+        let idx = (0..self.len).find(|&i| {
+            let range = self.ranges[i];
+            let aligned = align_up(range.start, layout.align());
+            aligned
+                .checked_add(layout.size())
+                .is_some_and(|end| end <= range.end)
+        })?;
+
+        let range = self.ranges[idx];
+        self.len -= 1;
+        self.ranges[idx] = self.ranges[self.len]; // swap-remove
+        Some(range)
+    }
+
+    /// Reinserts a free span, silently dropping it if the header is already tracking
+    /// [`MAX_FREE_RANGES`] spans - the arena leaks that span rather than corrupting its state.
+    fn push_free(&mut self, range: FreeRange) {
+        if range.start >= range.end || self.len == MAX_FREE_RANGES {
+            return;
+        }
+        self.ranges[self.len] = range;
+        self.len += 1;
+    }
+
+    fn coalesce(&mut self) {
+        let mut ranges: Vec<FreeRange> = self.ranges[..self.len].to_vec();
+        ranges.sort_by_key(|range| range.start);
+
+        let mut merged: Vec<FreeRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => merged.push(range),
+            }
+        }
+
+        self.len = merged.len();
+        self.ranges[..merged.len()].copy_from_slice(&merged);
+    }
+}
+
+struct ArenaHeaderGuard<'a> {
+    header: &'a mut ArenaHeader,
+}
+
+impl Deref for ArenaHeaderGuard<'_> {
+    type Target = ArenaHeader;
+
+    fn deref(&self) -> &Self::Target {
+        self.header
+    }
+}
+
+impl DerefMut for ArenaHeaderGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.header
+    }
+}
+
+impl Drop for ArenaHeaderGuard<'_> {
+    fn drop(&mut self) {
+        self.header.lock.store(0, Ordering::Release);
+    }
+}
+
+/// A sub-allocator handing out many typed [`ArenaBox`] handles from a single, large
+/// [`ShmemConf`], rather than one [`crate::ShmemBox`] per segment.
+///
+/// Tracks free space as a first-fit free list of byte ranges kept in a header at the arena's
+/// base (see [`ArenaHeader`]), coalescing adjacent ranges back together on [`ArenaBox`] drop, so
+/// every process attached to the segment shares one allocation state.
+pub struct ShmemArena<P: ShMemProvider = PosixShMemProvider> {
+    // `Rc` only keeps this process' mapping alive for as long as any `ArenaBox` is outstanding -
+    // the free list itself lives in the segment, not here.
+    conf: Rc<ShmemConf<P>>,
+}
+
+impl<P: ShMemProvider> ShmemArena<P> {
+    /// Builds an arena over `conf`, either initializing a fresh free list spanning the whole
+    /// segment (if `conf` created it, i.e. [`ShmemConf::is_owner`]) or attaching to the free list
+    /// a prior owner already wrote into it.
+    ///
+    /// [`ShmemConf::is_owner`] only tells us `conf` created the segment, not that its header has
+    /// been initialized yet - a peer can race in and call `ShmemArena::new` on the very same
+    /// segment before the owner's `ShmemArena::new` has gotten around to writing the header. To
+    /// stay correct across that window, a non-owner spins here until it observes
+    /// [`HEADER_READY`], rather than reading (and trusting) a possibly all-zero header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the segment is not larger than the header itself.
+    pub fn new(conf: ShmemConf<P>) -> Self {
+        let len = conf.mem().len();
+        let header_size = std::mem::size_of::<ArenaHeader>();
+        assert!(
+            len > header_size,
+            "ShmemArena requires a segment larger than the free-list header ({header_size} bytes)"
+        );
+
+        let header = conf.base_ptr() as *mut ArenaHeader;
+
+        if conf.is_owner() {
+            let mut ranges = [FreeRange { start: 0, end: 0 }; MAX_FREE_RANGES];
+            ranges[0] = FreeRange {
+                start: header_size,
+                end: len,
+            };
+            unsafe {
+                std::ptr::write(
+                    header,
+                    ArenaHeader {
+                        ready: AtomicU32::new(HEADER_UNINIT),
+                        lock: AtomicU32::new(0),
+                        len: 1,
+                        ranges,
+                    },
+                );
+                // published last, and with `Release` ordering, so a peer's `Acquire` spin-wait
+                // below only ever observes `HEADER_READY` after the writes above have landed.
+                (*header).ready.store(HEADER_READY, Ordering::Release);
+            }
+        } else {
+            // the segment exists (we didn't create it), but its owner may not have reached this
+            // same `ShmemArena::new` call yet - wait for it rather than racing ahead on a header
+            // that might still be all zeroes.
+            while unsafe { (*header).ready.load(Ordering::Acquire) } != HEADER_READY {
+                std::hint::spin_loop();
+            }
+        }
+
+        Self {
+            conf: Rc::new(conf),
+        }
+    }
+
+    fn header_ptr(&self) -> *mut ArenaHeader {
+        self.conf.base_ptr() as *mut ArenaHeader
+    }
+
+    /// First-fit allocates `layout` within the arena.
+    pub fn alloc(&self, layout: Layout) -> Option<NonNull<u8>> {
+        unsafe {
+            let mut header = ArenaHeader::lock(self.header_ptr());
+            let range = header.take_free(layout)?;
+            let aligned = align_up(range.start, layout.align());
+            let end = aligned + layout.size();
+
+            // first-fit split: keep whatever's left on either side of the carved-out slot.
+            if range.start < aligned {
+                header.push_free(FreeRange {
+                    start: range.start,
+                    end: aligned,
+                });
+            }
+            if end < range.end {
+                header.push_free(FreeRange {
+                    start: end,
+                    end: range.end,
+                });
+            }
+
+            NonNull::new(self.conf.base_ptr().add(aligned))
+        }
+    }
+
+    /// Returns a span previously handed out by [`Self::alloc`] to the free list.
+    ///
+    /// # Safety
+    ///
+    /// `ptr`/`layout` must match a still-outstanding allocation from this same arena.
+    pub unsafe fn dealloc(&self, ptr: NonNull<u8>, layout: Layout) {
+        dealloc_at(&self.conf, ptr, layout);
+    }
+
+    /// Allocates space for a `T`, returning a handle that returns the space to the arena's free
+    /// list (and drops the `T`) when it goes out of scope. Returns `None` if the arena has no
+    /// free span large enough.
+    pub fn boxed<T>(&self) -> Option<ArenaBox<T, P>> {
+        let ptr = self.alloc(Layout::new::<T>())?.cast();
+        Some(ArenaBox {
+            ptr,
+            conf: self.conf.clone(),
+        })
+    }
+}
+
+/// A typed handle into a [`ShmemArena`], analogous to [`crate::ShmemBox`] but backed by a shared
+/// sub-allocation instead of its own segment.
+pub struct ArenaBox<T, P: ShMemProvider = PosixShMemProvider> {
+    ptr: NonNull<T>,
+    conf: Rc<ShmemConf<P>>,
+}
+
+impl<T, P: ShMemProvider> ArenaBox<T, P> {
+    pub fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+}
+
+impl<T, P: ShMemProvider> Drop for ArenaBox<T, P> {
+    fn drop(&mut self) {
+        unsafe {
+            drop_in_place(self.ptr.as_ptr());
+            dealloc_at(&self.conf, self.ptr.cast(), Layout::new::<T>());
+        }
+    }
+}
+
+/// # Safety
+///
+/// `ptr`/`layout` must match a still-outstanding allocation from `conf`'s arena.
+unsafe fn dealloc_at<P: ShMemProvider>(conf: &ShmemConf<P>, ptr: NonNull<u8>, layout: Layout) {
+    let offset = ptr.as_ptr().offset_from(conf.base_ptr()) as usize;
+    let mut header = ArenaHeader::lock(conf.base_ptr() as *mut ArenaHeader);
+    header.push_free(FreeRange {
+        start: offset,
+        end: offset + layout.size(),
+    });
+    header.coalesce();
+}
+
+impl<T, P: ShMemProvider> Deref for ArenaBox<T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, P: ShMemProvider> DerefMut for ArenaBox<T, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.ptr.as_mut() }
+    }
+}
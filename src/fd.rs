@@ -0,0 +1,234 @@
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::os::unix::net::UnixStream;
+use std::ptr::{self, NonNull};
+
+#[cfg(not(target_os = "linux"))]
+use libc::{mmap, munmap, MAP_SHARED, PROT_READ, PROT_WRITE};
+use libc::{
+    c_void, cmsghdr, ftruncate, iovec, msghdr, recvmsg, sendmsg, CMSG_DATA, CMSG_FIRSTHDR,
+    CMSG_LEN, CMSG_SPACE, MAP_FAILED, SCM_RIGHTS, SOL_SOCKET,
+};
+
+use crate::provider::ShMem;
+use crate::ShmemError;
+
+/// Extension for [`ShMem`] implementations backed by a raw Unix file descriptor, enabling a
+/// segment to be shared by passing its fd to another process rather than by re-opening a name.
+pub trait RawFdShMem: ShMem {
+    /// The raw fd backing this segment.
+    fn as_raw_fd(&self) -> RawFd;
+
+    /// Maps an already-open `fd` (e.g. one just received over a Unix domain socket) of the given
+    /// `size`, without creating or naming a new segment.
+    fn from_raw_fd(fd: RawFd, size: i64) -> Result<Self, ShmemError>
+    where
+        Self: Sized;
+}
+
+/// Extension for [`ShMem`] implementations that can grow or shrink their backing segment in
+/// place, via [`crate::ShmemConf::resize`].
+pub trait Resizable: ShMem {
+    /// Grows or shrinks the segment to `new_size`, returning the new base pointer.
+    ///
+    /// # Safety
+    ///
+    /// Resizing can move the mapping to a new address; any raw pointer derived from the old
+    /// mapping - in this process or another - becomes dangling. Every process sharing this
+    /// segment must observe an out-of-band size update and re-map before dereferencing again.
+    unsafe fn resize(&mut self, new_size: i64) -> Result<*mut u8, ShmemError>;
+}
+
+/// `ftruncate`s `fd` to `new_size`, then remaps it: via `mremap` on Linux (which can grow a
+/// mapping in place or relocate it without a window where the data is unmapped), or a plain
+/// `munmap` + fresh `mmap` elsewhere.
+///
+/// `*addr`/`*size` are only ever left describing a mapping that is actually valid: on Linux,
+/// `mremap` updates them atomically as a unit on success; on failure they are untouched and the fd
+/// is `ftruncate`d back to the original size (panicking if that fails). On
+/// other Unix platforms, where resizing means `munmap`-then-`mmap`, a failure of the second
+/// `mmap` falls back to `ftruncate`ing the fd back to the *original* size (undoing the earlier
+/// `ftruncate(fd, new_size)`, which may have already shrunk it) and remapping that many bytes -
+/// restoring `*addr` to a valid (if unresized) mapping that the fd actually backs, rather than
+/// leaving `*addr` pointing at memory that was already unmapped (or, in the shrink case, past the
+/// fd's new end). If even that fallback fails, there is no valid mapping left to describe and
+/// this panics, matching how the rest of the crate treats an unrecoverable mmap/munmap failure.
+///
+/// # Safety
+///
+/// `*addr` must currently be mapped from `fd` over exactly `*size` bytes.
+pub(crate) unsafe fn resize_mapping(
+    fd: RawFd,
+    addr: &mut NonNull<c_void>,
+    size: &mut i64,
+    new_size: i64,
+) -> Result<(), ShmemError> {
+    if ftruncate(fd, new_size) < 0 {
+        return Err(ShmemError::AllocationFailedErr);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let remapped = libc::mremap(
+            addr.as_ptr(),
+            *size as usize,
+            new_size as usize,
+            libc::MREMAP_MAYMOVE,
+        );
+        if remapped == MAP_FAILED {
+            // undo the `ftruncate(fd, new_size)` above so the unchanged mapping of `*size` bytes
+            // is still fully backed by the fd.
+            if ftruncate(fd, *size) < 0 {
+                panic!(
+                    "failed to restore the original size after a failed resize; the segment has \
+                     no valid mapping left"
+                );
+            }
+            return Err(ShmemError::NullPointerErr);
+        }
+        *addr = NonNull::new(remapped).expect("mremap succeeded but returned a null pointer");
+        *size = new_size;
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        if munmap(addr.as_ptr(), *size as usize) != 0 {
+            return Err(ShmemError::CreateFailedErr);
+        }
+
+        let mapped = mmap(
+            ptr::null_mut(),
+            new_size as usize,
+            PROT_READ | PROT_WRITE,
+            MAP_SHARED,
+            fd,
+            0,
+        );
+        if mapped != MAP_FAILED {
+            *addr = NonNull::new(mapped).expect("mmap succeeded but returned a null pointer");
+            *size = new_size;
+            return Ok(());
+        }
+
+        // the earlier `ftruncate(fd, new_size)` may have shrunk the backing object below
+        // `*size`; undo it before remapping `*size` bytes, or a shrink-then-mmap-fails resize
+        // would leave `*addr` mapping a tail the fd no longer actually backs (SIGBUS on access).
+        if ftruncate(fd, *size) < 0 {
+            panic!(
+                "failed to restore the original size after a failed resize; the segment has no \
+                 valid mapping left"
+            );
+        }
+
+        let restored = mmap(
+            ptr::null_mut(),
+            *size as usize,
+            PROT_READ | PROT_WRITE,
+            MAP_SHARED,
+            fd,
+            0,
+        );
+        if restored == MAP_FAILED {
+            panic!(
+                "failed to restore the original mapping after a failed resize; the segment has \
+                 no valid mapping left"
+            );
+        }
+        *addr = NonNull::new(restored).expect("mmap succeeded but returned a null pointer");
+        // `*size` stays at its original value - the resize itself failed, only the mapping
+        // address changed while recovering from it.
+        Err(ShmemError::NullPointerErr)
+    }
+}
+
+/// The size a raw fd can't carry on its own, sent alongside it so the receiving end knows how
+/// much to `mmap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShmemDescriptor {
+    pub size: i64,
+}
+
+/// Sends `fd` as `SCM_RIGHTS` ancillary data over `socket`, with `size` as the accompanying
+/// message payload so the receiver (via [`recv_fd`]) knows how large a region to `mmap`.
+///
+/// `fd` is not closed by this call; the caller decides its fate (typically closing it once the
+/// receiver has acknowledged the transfer, since the kernel duplicates it into the receiving
+/// process independently).
+pub fn send_fd(socket: &UnixStream, fd: RawFd, size: i64) -> io::Result<()> {
+    let descriptor = ShmemDescriptor { size };
+    let payload = descriptor.size.to_ne_bytes();
+
+    let mut iov = iovec {
+        iov_base: payload.as_ptr() as *mut c_void,
+        iov_len: payload.len(),
+    };
+
+    let cmsg_space = unsafe { CMSG_SPACE(mem::size_of::<RawFd>() as u32) } as usize;
+    let mut cmsg_buf = vec![0u8; cmsg_space];
+
+    let mut msg: msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = ptr::addr_of_mut!(iov);
+    msg.msg_iovlen = 1;
+    msg.msg_control = cmsg_buf.as_mut_ptr() as *mut c_void;
+    msg.msg_controllen = cmsg_buf.len();
+
+    unsafe {
+        let cmsg: *mut cmsghdr = CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = SOL_SOCKET;
+        (*cmsg).cmsg_type = SCM_RIGHTS;
+        (*cmsg).cmsg_len = CMSG_LEN(mem::size_of::<RawFd>() as u32) as _;
+        ptr::write(CMSG_DATA(cmsg) as *mut RawFd, fd);
+    }
+
+    let sent = unsafe {
+        use std::os::unix::io::AsRawFd;
+        sendmsg(socket.as_raw_fd(), &msg, 0)
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Receives a `size`/fd pair sent by [`send_fd`], returning the size and the newly received
+/// (this process' own, distinct) file descriptor.
+pub fn recv_fd(socket: &UnixStream) -> io::Result<(i64, RawFd)> {
+    let mut payload = [0u8; mem::size_of::<i64>()];
+    let mut iov = iovec {
+        iov_base: payload.as_mut_ptr() as *mut c_void,
+        iov_len: payload.len(),
+    };
+
+    let cmsg_space = unsafe { CMSG_SPACE(mem::size_of::<RawFd>() as u32) } as usize;
+    let mut cmsg_buf = vec![0u8; cmsg_space];
+
+    let mut msg: msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = ptr::addr_of_mut!(iov);
+    msg.msg_iovlen = 1;
+    msg.msg_control = cmsg_buf.as_mut_ptr() as *mut c_void;
+    msg.msg_controllen = cmsg_buf.len();
+
+    let received = unsafe {
+        use std::os::unix::io::AsRawFd;
+        recvmsg(socket.as_raw_fd(), &mut msg, 0)
+    };
+    if received < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let fd = unsafe {
+        let cmsg: *mut cmsghdr = CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null() || (*cmsg).cmsg_type != SCM_RIGHTS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no SCM_RIGHTS fd received",
+            ));
+        }
+        ptr::read(CMSG_DATA(cmsg) as *const RawFd)
+    };
+
+    Ok((i64::from_ne_bytes(payload), fd))
+}
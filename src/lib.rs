@@ -2,32 +2,126 @@ use std::{
     error::Error,
     fmt::Display,
     ops::{Deref, DerefMut},
-    ptr::{self, drop_in_place, NonNull},
+    ptr::{drop_in_place, NonNull},
+    sync::atomic::{AtomicU32, Ordering},
 };
-use std::ffi::CString;
-use libc::{c_char, c_void, close, ftruncate, mmap, munmap, shm_open, shm_unlink, MAP_SHARED, O_CREAT, O_EXCL, O_RDWR, PROT_READ, PROT_WRITE};
 
-pub struct Builder {
+mod arena;
+#[cfg(unix)]
+mod fd;
+#[cfg(target_os = "linux")]
+mod memfd;
+mod posix;
+mod provider;
+mod to_shmem;
+#[cfg(windows)]
+mod win32;
+
+pub use arena::{ArenaBox, ShmemArena};
+#[cfg(unix)]
+use fd::{RawFdShMem, Resizable};
+#[cfg(unix)]
+pub use fd::{recv_fd, send_fd, ShmemDescriptor};
+#[cfg(target_os = "linux")]
+pub use memfd::{MemfdSeals, MemfdShMemProvider};
+pub use posix::PosixShMemProvider;
+pub use provider::{NameAttachable, ShMem, ShMemProvider};
+pub use to_shmem::{SharedMemoryBuilder, ToShmem};
+#[cfg(windows)]
+pub use win32::Win32ShMemProvider;
+
+pub struct Builder<P: ShMemProvider = PosixShMemProvider> {
     id: String,
+    provider: P,
+    ref_counted: bool,
 }
 
-impl Builder {
+impl Builder<PosixShMemProvider> {
+    /// Starts building a POSIX-backed shared memory segment.
+    ///
+    /// To build against a different [`ShMemProvider`] (e.g. `Win32ShMemProvider` on Windows), use
+    /// [`Builder::with_provider`] instead.
     pub fn new(id: &str) -> Self {
+        Self::with_provider(id)
+    }
+
+    /// Switches to an anonymous, Linux `memfd_create`-backed segment identified by a file
+    /// descriptor rather than a `shm_open` name - see [`MemfdShMemProvider`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Builder::ref_counted`] was already called: every `memfd_create` produces a
+    /// brand new, unrelated fd, so there is no peer for a `memfd`-backed segment to share a
+    /// counter with - [`MemfdShMemProvider`] doesn't implement [`NameAttachable`], so this
+    /// combination can't even be expressed the other way around (`.anonymous().ref_counted()`
+    /// fails to compile).
+    #[cfg(target_os = "linux")]
+    pub fn anonymous(self) -> Builder<MemfdShMemProvider> {
+        assert!(
+            !self.ref_counted,
+            "ref_counted() has no effect on anonymous (memfd) segments - every open() creates an \
+             unrelated fd, so there is no peer to share the counter with"
+        );
+        Builder {
+            id: self.id,
+            provider: MemfdShMemProvider,
+            ref_counted: self.ref_counted,
+        }
+    }
+}
+
+impl<P: ShMemProvider> Builder<P> {
+    /// Starts building a shared memory segment backed by `P`.
+    pub fn with_provider(id: &str) -> Self {
         Self {
             id: String::from(id),
+            provider: P::default(),
+            ref_counted: false,
         }
     }
 
-    pub fn with_size(self, size: i64) -> BuilderWithSize {
-        BuilderWithSize { id: self.id, size }
+    pub fn with_size(self, size: i64) -> BuilderWithSize<P> {
+        BuilderWithSize {
+            id: self.id,
+            provider: self.provider,
+            ref_counted: self.ref_counted,
+            size,
+        }
+    }
+}
+
+impl<P: NameAttachable> Builder<P> {
+    /// Switches the segment to reference-counted cleanup: instead of a single process being the
+    /// `is_owner` that unlinks the segment, every `open` attaches to a shared counter (held in a
+    /// companion segment named `"{id}_refcount"`) and the segment is only unlinked once the last
+    /// attached `ShmemConf` is dropped.
+    ///
+    /// This makes `ShmemBox::own`/`ShmemBox::leak` no-ops for the cleanup decision, since
+    /// ownership is no longer tracked per-process.
+    ///
+    /// Only available for providers whose segments are actually [`NameAttachable`] - there is
+    /// nothing for a shared counter to mean on a provider where every `open` creates an unrelated
+    /// segment (e.g. [`MemfdShMemProvider`]).
+    ///
+    /// # Caveats
+    ///
+    /// The first `open` to create the primary segment initializes the counter to `1` only after
+    /// the segment itself exists; a peer that manages to `open` the segment between those two
+    /// steps will see an uninitialized counter. Callers must ensure the creator has fully
+    /// returned from `open` before any peer attempts to attach.
+    pub fn ref_counted(mut self) -> Self {
+        self.ref_counted = true;
+        self
     }
 }
 
-pub struct BuilderWithSize {
+pub struct BuilderWithSize<P: ShMemProvider = PosixShMemProvider> {
     id: String,
+    provider: P,
+    ref_counted: bool,
     size: i64,
 }
-impl BuilderWithSize {
+impl<P: ShMemProvider> BuilderWithSize<P> {
     /// Ensures a shared memory using the specified `size` and `flink_id` and mapping it to the
     /// virtual address of the process memory.
     ///
@@ -62,67 +156,80 @@ impl BuilderWithSize {
     ///     Ok(())
     /// }
     ///```
-    pub fn open(self) -> Result<ShmemConf, ShmemError> {
-        let (fd, is_owner) = unsafe {
-            let storage_id = CString::new(self.id.as_bytes()).unwrap();  // Ensure proper null termination
-
-            // open the existing shared memory if exists
-            let fd = shm_open(storage_id.as_ptr(), O_CREAT | O_EXCL | O_RDWR, 0o600);
-
-            // shared memory didn't exist
-            if fd >= 0 {
-                // allocate the shared memory with required size
-                let res = ftruncate(fd, self.size);
-                if res < 0 {
-                    return Err(ShmemError::AllocationFailedErr);
-                }
+    pub fn open(self) -> Result<ShmemConf<P>, ShmemError> {
+        let (mem, is_owner) = self.provider.open(&self.id, self.size)?;
 
-                (fd, true)
-            } else {
-                let err = std::io::Error::last_os_error();
-                if err.raw_os_error() == Some(libc::EEXIST) {
-                    // The shared memory object already exists, so open it without O_EXCL
-                    let fd = shm_open(storage_id.as_ptr(), O_RDWR, 0o600);
-                    if fd < 0 {
-                        return Err(ShmemError::CreateFailedErr);
-                    }
-                    (fd, false)
-                } else {
-                    return Err(ShmemError::CreateFailedErr);
-                }
-            }
+        // built immediately, before attaching the ref-count companion segment below, so that if
+        // attaching fails `conf`'s own `Drop` (which unlinks `mem` when `is_owner`) runs instead
+        // of `mem` being silently leaked by the `?` on a freshly-created, owned segment.
+        let mut conf = ShmemConf {
+            is_owner,
+            provider: self.provider,
+            mem,
+            ref_count: None,
         };
 
-        let null = ptr::null_mut();
-        let addr = unsafe { mmap(null, self.size as usize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) };
+        if self.ref_counted {
+            conf.ref_count = Some(RefCount::attach(&conf.provider, &self.id)?);
+        }
 
-        Ok(ShmemConf {
-            id: self.id,
-            is_owner,
-            fd,
-            addr: NonNull::new(addr as *mut _).ok_or(ShmemError::NullPointerErr)?,
-            size: self.size,
-        })
+        Ok(conf)
     }
 }
 
-/// A representation of a ***mapped*** shared memory.
+/// The companion `"{id}_refcount"` segment backing [`Builder::ref_counted`].
 #[derive(Debug)]
-pub struct ShmemConf {
-    /// `flink_id` of the shared memory to be created on the system
+struct RefCount<P: ShMemProvider> {
     id: String,
+    mem: P::Mem,
+}
+
+impl<P: ShMemProvider> RefCount<P> {
+    /// Attaches to (creating if necessary) the refcount segment for `id`, bumping the shared
+    /// counter to reflect this attachment.
+    fn attach(provider: &P, id: &str) -> Result<Self, ShmemError> {
+        let counter_id = format!("{id}_refcount");
+        let (mem, is_owner) = provider.open(&counter_id, std::mem::size_of::<AtomicU32>() as i64)?;
+
+        let counter = unsafe { &*(mem.as_ptr() as *const AtomicU32) };
+        if is_owner {
+            counter.store(1, Ordering::SeqCst);
+        } else {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }
+
+        Ok(Self { id: counter_id, mem })
+    }
+
+    fn counter(&self) -> &AtomicU32 {
+        unsafe { &*(self.mem.as_ptr() as *const AtomicU32) }
+    }
+
+    /// Detaches from the counter, returning `true` if this was the last attached handle.
+    fn detach(&self) -> bool {
+        self.counter().fetch_sub(1, Ordering::SeqCst) == 1
+    }
+}
+
+/// A representation of a ***mapped*** shared memory.
+///
+/// Generic over the [`ShMemProvider`] backing it; defaults to [`PosixShMemProvider`] so existing
+/// code using `ShmemConf` unqualified keeps working unchanged.
+#[derive(Debug)]
+pub struct ShmemConf<P: ShMemProvider = PosixShMemProvider> {
     /// Wether or not this `ShmemConf` is the owner of the shared memory.
     /// This field is set to true when the shared memory is created by this `ShmemConf`
     is_owner: bool,
-    /// File descriptor of the allocated shared memory 
-    fd: i32,
-    /// Pointer to the shared memory
-    addr: NonNull<()>,
-    /// Size of the allocation
-    size: i64,
+    /// The provider used to create `mem`, kept around so `Drop` can unlink through it.
+    provider: P,
+    /// The mapped shared memory segment.
+    mem: P::Mem,
+    /// Present when the segment was opened through [`Builder::ref_counted`]; once set, this (and
+    /// not `is_owner`) decides when the segment is actually unlinked.
+    ref_count: Option<RefCount<P>>,
 }
 
-impl ShmemConf {
+impl<P: ShMemProvider> ShmemConf<P> {
     /// Converts `ShmemConf`'s raw pointer to a boxed pointer of type `T`.
     ///
     /// # Safety
@@ -165,14 +272,14 @@ impl ShmemConf {
     ///     // to the same location.
     ///     *boxed_barrow_val = 3;
     ///     assert_eq!(*boxed_val, 3);
-    ///     
+    ///
     ///     Ok(())
     /// }
     ///
     /// ```
-    pub unsafe fn boxed<T>(self) -> ShmemBox<T> {
+    pub unsafe fn boxed<T>(self) -> ShmemBox<T, P> {
         ShmemBox {
-            ptr: self.addr.cast(),
+            ptr: NonNull::new_unchecked(self.mem.as_ptr() as *mut T),
             conf: self,
         }
     }
@@ -180,14 +287,102 @@ impl ShmemConf {
     pub fn is_owner(&self) -> bool {
         self.is_owner
     }
+
+    /// The underlying mapped segment, for provider-specific extensions (e.g. memfd sealing).
+    pub(crate) fn mem(&self) -> &P::Mem {
+        &self.mem
+    }
+
+    /// A bump allocator writing into this segment's mapped region, for serializing [`ToShmem`]
+    /// values into it instead of one POD `T` at a time via [`Self::boxed`].
+    pub fn shared_memory_builder(&self) -> SharedMemoryBuilder {
+        // # Safety
+        //
+        // `self.mem`'s mapping covers exactly `self.mem.len()` bytes and outlives the builder,
+        // which borrows `self`.
+        unsafe { SharedMemoryBuilder::new(self.mem.as_ptr(), self.mem.len()) }
+    }
+
+    /// The base address [`Self::shared_memory_builder`] allocates relative to - pass this and a
+    /// peer's own base address (after it maps the same segment, e.g. via fd-passing) to
+    /// [`rebase_shmem`] to fix up a [`ToShmem`] value built against this mapping.
+    pub fn base_ptr(&self) -> *mut u8 {
+        self.mem.as_ptr()
+    }
+}
+
+/// Shifts every pointer a [`ToShmem`] value holds from `old_base` to `new_base`, so a value built
+/// through a [`SharedMemoryBuilder`] over one mapping of a segment (via [`ShmemConf::base_ptr`])
+/// becomes valid for another process' mapping of the same segment at a different address.
+///
+/// # Safety
+///
+/// `value` must be exactly what `T::to_shmem` produced (or a byte-for-byte copy of it) against a
+/// builder whose base was `old_base`, and `new_base` must be the base address of a mapping of
+/// that same arena in the process that will use `value` from now on.
+pub unsafe fn rebase_shmem<T: ToShmem>(value: &mut T, old_base: *mut u8, new_base: *mut u8) {
+    T::rebase(value, new_base as isize - old_base as isize);
+}
+
+#[cfg(unix)]
+impl<P: ShMemProvider> ShmemConf<P>
+where
+    P::Mem: RawFdShMem,
+{
+    /// The raw fd backing this segment.
+    pub fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+        self.mem.as_raw_fd()
+    }
+
+    /// Consumes this `ShmemConf`, handing ownership of its raw fd to the caller without running
+    /// any cleanup (unmapping, unlinking, or closing the fd) - the caller becomes responsible for
+    /// the fd's lifetime, typically to pass it to another process.
+    pub fn into_raw_fd(self) -> std::os::unix::io::RawFd {
+        let fd = self.mem.as_raw_fd();
+        std::mem::forget(self);
+        fd
+    }
+
+    /// Maps an already-open `fd` (e.g. one received via [`recv_fd`]) of the given `size`,
+    /// without touching `shm_open`/`memfd_create`. The resulting `ShmemConf` never unlinks
+    /// anything on drop (there is no name it could own), only unmaps and closes the fd.
+    pub fn from_raw_fd(fd: std::os::unix::io::RawFd, size: i64) -> Result<Self, ShmemError> {
+        Ok(Self {
+            is_owner: false,
+            provider: P::default(),
+            mem: P::Mem::from_raw_fd(fd, size)?,
+            ref_count: None,
+        })
+    }
+}
+
+#[cfg(unix)]
+impl<P: ShMemProvider> ShmemConf<P>
+where
+    P::Mem: Resizable,
+{
+    /// Grows or shrinks the segment to `new_size` in place (`ftruncate` followed by `mremap` on
+    /// Linux, or `munmap` + a fresh `mmap` elsewhere), returning the new base pointer.
+    ///
+    /// # Safety
+    ///
+    /// Resizing can relocate the mapping, so every raw pointer derived from the old base -
+    /// including ones held by [`ShmemBox`]es built with [`ShmemConf::boxed`] or values written
+    /// through [`ShmemConf::shared_memory_builder`] - becomes dangling. The caller must rebuild
+    /// any such pointer from the returned base (e.g. via [`rebase_shmem`] for `ToShmem` values)
+    /// before using it again, and other processes sharing this segment must observe the new size
+    /// (communicated out of band) and re-map before dereferencing into it.
+    pub unsafe fn resize(&mut self, new_size: i64) -> Result<*mut u8, ShmemError> {
+        self.mem.resize(new_size)
+    }
 }
 
 /// # Safety
 ///
 /// Shared memory is shared between processes.
 /// If it can withstand multiple processes mutating it, it can sure handle a thread or two!
-unsafe impl<T: Sync> Sync for ShmemBox<T> {}
-unsafe impl<T: Send> Send for ShmemBox<T> {}
+unsafe impl<T: Sync, P: ShMemProvider> Sync for ShmemBox<T, P> {}
+unsafe impl<T: Send, P: ShMemProvider> Send for ShmemBox<T, P> {}
 
 /// A safe and typed wrapper for shared memory
 ///
@@ -196,12 +391,12 @@ unsafe impl<T: Send> Send for ShmemBox<T> {}
 ///
 /// When ShmemBox<T> goes out of scope, the cleanup process of the shared memory is done.
 #[derive(Debug)]
-pub struct ShmemBox<T> {
+pub struct ShmemBox<T, P: ShMemProvider = PosixShMemProvider> {
     ptr: NonNull<T>,
-    conf: ShmemConf,
+    conf: ShmemConf<P>,
 }
 
-impl<T> ShmemBox<T> {
+impl<T, P: ShMemProvider> ShmemBox<T, P> {
     /// Owns the shared memory. this would result in shared memory cleanup when this pointer goes
     /// out of scope.
     ///
@@ -217,10 +412,10 @@ impl<T> ShmemBox<T> {
     ///         .with_size(mem::size_of::<i32>() as i64)
     ///         .open()?;
     ///     let mut boxed_val = unsafe { shared_mem.boxed::<i32>() };
-    ///     
+    ///
     ///     // leaking the shared memory to prevent `shared_mem` from cleaning it up.
     ///     ShmemBox::leak(boxed_val);
-    ///     
+    ///
     ///     // shared memory is already present on the machine. `shared_mem` does not own the
     ///     // shared memory.
     ///     let shared_mem = shmem::Builder::new("flink_test_own")
@@ -259,10 +454,10 @@ impl<T> ShmemBox<T> {
     ///         .with_size(mem::size_of::<i32>() as i64)
     ///         .open()?;
     ///     let mut boxed_val = unsafe { shared_mem.boxed::<i32>() };
-    ///     
+    ///
     ///     // leaking the shared memory to prevent `shared_mem` from cleaning it up.
     ///     ShmemBox::leak(boxed_val);
-    ///     
+    ///
     ///     // shared memory is already present on the machine. `shared_mem` does not own the
     ///     // shared memory.
     ///     let shared_mem = shmem::Builder::new("flink_test_leak")
@@ -282,13 +477,13 @@ impl<T> ShmemBox<T> {
         // disabling cleanup for shared memory
         shmem_box.conf.is_owner = false;
     }
-    
+
     pub fn as_ptr(&self) -> *mut T {
         self.ptr.as_ptr()
     }
 }
 
-impl<T> Drop for ShmemBox<T> {
+impl<T, P: ShMemProvider> Drop for ShmemBox<T, P> {
     fn drop(&mut self) {
         if self.conf.is_owner {
             // # Safety
@@ -299,34 +494,36 @@ impl<T> Drop for ShmemBox<T> {
         }
     }
 }
-impl Drop for ShmemConf {
+impl<P: ShMemProvider> Drop for ShmemConf<P> {
     fn drop(&mut self) {
+        // with ref-counting, cleanup happens once the shared counter reaches zero rather than
+        // based on `is_owner`, since ownership no longer belongs to a single process.
+        let should_unlink = match &self.ref_count {
+            Some(ref_count) => ref_count.detach(),
+            None => self.is_owner,
+        };
+
         // # Safety
         //
         // if current process is the owner of the shared_memory,i.e. creator of the shared
-        // memory, then it should clean up after.
-        // the procedure is as follow:
-        // 1. unmap the shared memory from processes virtual address space.
-        // 2. unlink the shared memory completely from the os if self is the owner
-        // 3. close the file descriptor of the shared memory
-        if unsafe { munmap(self.addr.as_ptr() as *mut c_void, self.size as usize) } != 0 {
-            panic!("failed to unmap shared memory from the virtual memory space")
-        }
-
-        if self.is_owner {
-            let storage_id: *const c_char = self.id.as_bytes().as_ptr() as *const c_char;
-            if unsafe { shm_unlink(storage_id) } != 0 {
+        // memory, then it should clean up after by unlinking it from the system through its
+        // provider. unmapping (and closing whatever descriptor/handle backs the mapping) is
+        // `P::Mem`'s own responsibility and happens when `self.mem` (and `self.ref_count`) are
+        // dropped right after this.
+        if should_unlink {
+            if self.provider.unlink(self.mem.id()).is_err() {
                 panic!("failed to reclaim shared memory")
             }
-        }
-
-        if unsafe { close(self.fd) } != 0 {
-            panic!("failed to close shared memory file descriptor")
+            if let Some(ref_count) = &self.ref_count {
+                if self.provider.unlink(&ref_count.id).is_err() {
+                    panic!("failed to reclaim shared memory refcount segment")
+                }
+            }
         }
     }
 }
 
-impl<T> Deref for ShmemBox<T> {
+impl<T, P: ShMemProvider> Deref for ShmemBox<T, P> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -334,7 +531,7 @@ impl<T> Deref for ShmemBox<T> {
     }
 }
 
-impl<T> DerefMut for ShmemBox<T> {
+impl<T, P: ShMemProvider> DerefMut for ShmemBox<T, P> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { self.ptr.as_mut() }
     }
@@ -423,4 +620,249 @@ mod tests {
         // assert that the new process mutated the shared memory
         assert_eq!(data.val, new_val);
     }
+
+    #[test]
+    fn ref_counted_keeps_segment_alive_until_last_detach() {
+        #[derive(Debug)]
+        struct Data {
+            val: i32,
+        }
+
+        let first = Builder::new("test-shmem-box-ref-counted")
+            .ref_counted()
+            .with_size(std::mem::size_of::<Data>() as i64)
+            .open()
+            .unwrap();
+        let mut first = unsafe { first.boxed::<Data>() };
+        first.val = 1;
+
+        let second = Builder::new("test-shmem-box-ref-counted")
+            .ref_counted()
+            .with_size(std::mem::size_of::<Data>() as i64)
+            .open()
+            .unwrap();
+        let second = unsafe { second.boxed::<Data>() };
+        assert_eq!(second.val, 1);
+
+        // dropping `first` must not unlink the segment: `second` is still attached.
+        drop(first);
+
+        let third = Builder::new("test-shmem-box-ref-counted")
+            .ref_counted()
+            .with_size(std::mem::size_of::<Data>() as i64)
+            .open()
+            .unwrap();
+        let third = unsafe { third.boxed::<Data>() };
+        assert_eq!(third.val, 1);
+
+        drop(second);
+        drop(third);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[should_panic(expected = "ref_counted() has no effect on anonymous")]
+    fn anonymous_rejects_a_prior_ref_counted() {
+        let _ = Builder::new("test-shmem-anonymous-ref-counted")
+            .ref_counted()
+            .anonymous()
+            .with_size(std::mem::size_of::<i32>() as i64);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn memfd_seal_is_observable_via_seals() {
+        let shmconf = Builder::new("test-shmem-memfd-seal")
+            .anonymous()
+            .with_size(std::mem::size_of::<i32>() as i64)
+            .open()
+            .unwrap();
+
+        assert!(!shmconf.seals().unwrap().contains(MemfdSeals::GROW));
+
+        // `MemfdSeals::WRITE` is deliberately left out here: the kernel rejects it while this
+        // `ShmemConf` still holds a writable mapping of the fd (`EBUSY`).
+        shmconf
+            .seal(MemfdSeals::GROW | MemfdSeals::SHRINK)
+            .unwrap();
+
+        let seals = shmconf.seals().unwrap();
+        assert!(seals.contains(MemfdSeals::GROW));
+        assert!(seals.contains(MemfdSeals::SHRINK));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn fd_passing_round_trip_over_unix_socket() {
+        use std::os::unix::net::UnixStream;
+
+        let size = std::mem::size_of::<i32>() as i64;
+        let shmconf = Builder::new("test-shmem-fd-passing")
+            .anonymous()
+            .with_size(size)
+            .open()
+            .unwrap();
+        let fd = shmconf.as_raw_fd();
+        let mut data = unsafe { shmconf.boxed::<i32>() };
+        *data = 42;
+
+        let (sender, receiver) = UnixStream::pair().unwrap();
+        send_fd(&sender, fd, size).unwrap();
+
+        let (received_size, received_fd) = recv_fd(&receiver).unwrap();
+        assert_eq!(received_size, size);
+
+        let received_conf =
+            ShmemConf::<MemfdShMemProvider>::from_raw_fd(received_fd, received_size).unwrap();
+        let received = unsafe { received_conf.boxed::<i32>() };
+        assert_eq!(*received, 42);
+    }
+
+    #[test]
+    fn to_shmem_vec_of_strings_round_trips() {
+        let shmconf = Builder::new("test-shmem-to-shmem")
+            .with_size(4096)
+            .open()
+            .unwrap();
+
+        let original: Vec<String> = vec!["hello".into(), "shared memory".into()];
+        let mut builder = shmconf.shared_memory_builder();
+        let relocated = original.to_shmem(&mut builder).unwrap();
+        assert_eq!(*relocated, original);
+
+        // a bitwise copy of the (ptr, len, cap) header - still describing `shmconf`'s mapping.
+        let mut header_copy: Vec<String> = unsafe { std::ptr::read(&*relocated) };
+
+        // simulate a peer mapping the same bytes at a different address: copy the raw bytes
+        // elsewhere and rebase the header against the new base.
+        let mut other_mapping = vec![0u8; 4096];
+        unsafe {
+            std::ptr::copy_nonoverlapping(shmconf.base_ptr(), other_mapping.as_mut_ptr(), 4096);
+            rebase_shmem(&mut header_copy, shmconf.base_ptr(), other_mapping.as_mut_ptr());
+        }
+
+        assert_eq!(header_copy, original);
+        std::mem::forget(header_copy); // points into `other_mapping`, not the heap allocator
+    }
+
+    #[test]
+    fn resize_grows_segment_and_preserves_contents() {
+        let mut shmconf = Builder::new("test-shmem-resize")
+            .with_size(64)
+            .open()
+            .unwrap();
+
+        unsafe { std::ptr::write_bytes(shmconf.base_ptr(), 0xab, 64) };
+
+        let new_base = unsafe { shmconf.resize(4096).unwrap() };
+        assert_eq!(new_base, shmconf.base_ptr());
+
+        let grown = unsafe { std::slice::from_raw_parts(shmconf.base_ptr(), 4096) };
+        assert!(grown[..64].iter().all(|&b| b == 0xab));
+    }
+
+    #[test]
+    fn arena_reuses_freed_space_for_many_objects() {
+        let shmconf = Builder::new("test-shmem-arena")
+            .with_size(4096)
+            .open()
+            .unwrap();
+        let arena = ShmemArena::new(shmconf);
+
+        let mut a = arena.boxed::<i32>().unwrap();
+        let mut b = arena.boxed::<i64>().unwrap();
+        *a = 1;
+        *b = 2;
+        assert_eq!(*a, 1);
+        assert_eq!(*b, 2);
+        let a_ptr = a.as_ptr() as *mut u8;
+
+        drop(a);
+        drop(b);
+
+        // the freed spans coalesced back into the span they were carved from, so a larger object
+        // lands first-fit exactly where `a` was.
+        let mut c = arena.boxed::<[u8; 16]>().unwrap();
+        assert_eq!(c.as_ptr() as *mut u8, a_ptr);
+        *c = [7u8; 16];
+        assert_eq!(*c, [7u8; 16]);
+    }
+
+    #[test]
+    fn arena_free_list_is_shared_across_mappings_of_the_same_segment() {
+        let conf1 = Builder::new("test-shmem-arena-shared")
+            .with_size(4096)
+            .open()
+            .unwrap();
+        assert!(conf1.is_owner());
+        let arena1 = ShmemArena::new(conf1);
+
+        let mut a = arena1.boxed::<i64>().unwrap();
+        *a = 42;
+
+        // A second mapping of the *same* segment - as a second process attaching by name would
+        // get - must see arena1's allocation already reserved in the shared header, not an
+        // independent free list starting from scratch.
+        let conf2 = Builder::new("test-shmem-arena-shared")
+            .with_size(4096)
+            .open()
+            .unwrap();
+        assert!(!conf2.is_owner());
+        let arena2 = ShmemArena::new(conf2);
+
+        let mut b = arena2.boxed::<i64>().unwrap();
+        *b = 7;
+
+        // if the free list were process-local, `b` would have landed on the same bytes as `a`.
+        assert_eq!(*a, 42);
+        assert_eq!(*b, 7);
+    }
+
+    #[test]
+    fn arena_new_waits_for_owner_to_finish_initializing_header() {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        // `ShmemArena`/`ShmemConf` aren't `Send` (their whole point is that the *segment*, not
+        // any in-process handle to it, is what's shared), so the owner creates, initializes and
+        // keeps its own `ShmemConf` entirely within its own thread - only `()` crosses over via
+        // the channels below, same as a real separate process would only share the segment's
+        // name.
+        let id = "test-shmem-arena-init-race";
+        let (created_tx, created_rx) = mpsc::channel();
+        let (finish_tx, finish_rx) = mpsc::channel::<()>();
+
+        let owner = std::thread::spawn(move || {
+            let owner_conf = Builder::new(id).with_size(4096).open().unwrap();
+            assert!(owner_conf.is_owner());
+            created_tx.send(()).unwrap();
+
+            // simulate the gap between `BuilderWithSize::open` creating the segment and the
+            // owner getting around to calling `ShmemArena::new` on it.
+            std::thread::sleep(Duration::from_millis(50));
+            let owner_arena = ShmemArena::new(owner_conf);
+
+            // keep the segment (and thus its header) alive until the peer below is done with it.
+            finish_rx.recv().unwrap();
+            drop(owner_arena);
+        });
+
+        created_rx.recv().unwrap();
+
+        // attaching to the same (already-created) segment while the owner above hasn't called
+        // `ShmemArena::new` yet must block here rather than reading a zeroed header and treating
+        // the arena as permanently full.
+        let peer_conf = Builder::new(id).with_size(4096).open().unwrap();
+        assert!(!peer_conf.is_owner());
+        let peer_arena = ShmemArena::new(peer_conf);
+
+        let mut value = peer_arena.boxed::<i64>().expect("header is ready by now");
+        *value = 9;
+        assert_eq!(*value, 9);
+        drop(value);
+        drop(peer_arena);
+
+        finish_tx.send(()).unwrap();
+        owner.join().unwrap();
+    }
 }
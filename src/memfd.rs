@@ -0,0 +1,207 @@
+use std::ffi::CString;
+use std::ops::{BitOr, BitOrAssign};
+use std::ptr::{self, NonNull};
+
+use libc::{
+    c_void, close, fcntl, ftruncate, memfd_create, mmap, munmap, F_ADD_SEALS, F_GET_SEALS,
+    F_SEAL_GROW, F_SEAL_SEAL, F_SEAL_SHRINK, F_SEAL_WRITE, MAP_FAILED, MAP_SHARED,
+    MFD_ALLOW_SEALING, PROT_READ, PROT_WRITE,
+};
+
+use crate::fd::{resize_mapping, RawFdShMem, Resizable};
+use crate::provider::{ShMem, ShMemProvider};
+use crate::ShmemError;
+
+/// A [`ShMemProvider`] backed by Linux `memfd_create`.
+///
+/// Unlike [`crate::PosixShMemProvider`], the resulting segment has no `shm_open` name a peer can
+/// re-`open`: every call to `open` creates a fresh, anonymous segment, meant to be shared by
+/// passing its file descriptor around (see `ShmemConf::as_raw_fd`/`ShmemConf::from_raw_fd`)
+/// rather than by name.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MemfdShMemProvider;
+
+impl ShMemProvider for MemfdShMemProvider {
+    type Mem = MemfdShMem;
+
+    fn open(&self, id: &str, size: i64) -> Result<(Self::Mem, bool), ShmemError> {
+        let name = CString::new(id.as_bytes()).unwrap();
+
+        let fd = unsafe { memfd_create(name.as_ptr(), MFD_ALLOW_SEALING) };
+        if fd < 0 {
+            return Err(ShmemError::CreateFailedErr);
+        }
+
+        if unsafe { ftruncate(fd, size) } < 0 {
+            unsafe { close(fd) };
+            return Err(ShmemError::AllocationFailedErr);
+        }
+
+        let addr = unsafe { mmap(ptr::null_mut(), size as usize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) };
+        if addr == MAP_FAILED {
+            unsafe { close(fd) };
+            return Err(ShmemError::NullPointerErr);
+        }
+        // `MAP_FAILED` ((void*)-1) is not null, so it was just ruled out above.
+        let addr = NonNull::new(addr).ok_or(ShmemError::NullPointerErr)?;
+
+        // every `memfd_create` is a brand new, unshared segment, so this call always "creates" it.
+        Ok((
+            MemfdShMem {
+                id: String::from(id),
+                fd,
+                addr,
+                size,
+            },
+            true,
+        ))
+    }
+
+    fn unlink(&self, _id: &str) -> Result<(), ShmemError> {
+        // memfd segments have no filesystem name to unlink; the backing anonymous file is freed
+        // once every fd referencing it (across every process) is closed.
+        Ok(())
+    }
+}
+
+/// A shared memory segment backed by an anonymous `memfd_create` file.
+#[derive(Debug)]
+pub struct MemfdShMem {
+    id: String,
+    fd: i32,
+    addr: NonNull<c_void>,
+    size: i64,
+}
+
+impl MemfdShMem {
+    /// The raw file descriptor backing this segment.
+    pub fn fd(&self) -> i32 {
+        self.fd
+    }
+}
+
+impl ShMem for MemfdShMem {
+    fn as_ptr(&self) -> *mut u8 {
+        self.addr.as_ptr() as *mut u8
+    }
+
+    fn len(&self) -> usize {
+        self.size as usize
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl RawFdShMem for MemfdShMem {
+    fn as_raw_fd(&self) -> i32 {
+        self.fd
+    }
+
+    fn from_raw_fd(fd: i32, size: i64) -> Result<Self, ShmemError> {
+        let addr = unsafe { mmap(ptr::null_mut(), size as usize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) };
+        if addr == MAP_FAILED {
+            return Err(ShmemError::NullPointerErr);
+        }
+
+        Ok(Self {
+            id: format!("fd:{fd}"),
+            fd,
+            addr: NonNull::new(addr).ok_or(ShmemError::NullPointerErr)?,
+            size,
+        })
+    }
+}
+
+impl Resizable for MemfdShMem {
+    unsafe fn resize(&mut self, new_size: i64) -> Result<*mut u8, ShmemError> {
+        resize_mapping(self.fd, &mut self.addr, &mut self.size, new_size)?;
+        Ok(self.addr.as_ptr() as *mut u8)
+    }
+}
+
+impl Drop for MemfdShMem {
+    fn drop(&mut self) {
+        if unsafe { munmap(self.addr.as_ptr(), self.size as usize) } != 0 {
+            panic!("failed to unmap shared memory from the virtual memory space")
+        }
+
+        if unsafe { close(self.fd) } != 0 {
+            panic!("failed to close shared memory file descriptor")
+        }
+    }
+}
+
+/// A bitset of `memfd` seals (`fcntl(F_ADD_SEALS)`/`F_GET_SEALS`), applied via
+/// [`crate::ShmemConf::seal`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemfdSeals(libc::c_int);
+
+impl MemfdSeals {
+    /// Prevents the segment from growing (`F_SEAL_GROW`).
+    pub const GROW: Self = Self(F_SEAL_GROW);
+    /// Prevents the segment from shrinking (`F_SEAL_SHRINK`).
+    pub const SHRINK: Self = Self(F_SEAL_SHRINK);
+    /// Prevents any further writes to the segment (`F_SEAL_WRITE`).
+    pub const WRITE: Self = Self(F_SEAL_WRITE);
+    /// Prevents any further seals from being applied (`F_SEAL_SEAL`).
+    pub const SEAL: Self = Self(F_SEAL_SEAL);
+
+    /// Whether every seal in `other` is also set in `self`.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    fn raw(self) -> libc::c_int {
+        self.0
+    }
+
+    fn from_raw(raw: libc::c_int) -> Self {
+        Self(raw)
+    }
+}
+
+impl BitOr for MemfdSeals {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for MemfdSeals {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl crate::ShmemConf<MemfdShMemProvider> {
+    /// Applies `seals` to this segment via `fcntl(fd, F_ADD_SEALS, ...)`.
+    ///
+    /// The fd must have been created with `MFD_ALLOW_SEALING`, which every segment opened through
+    /// [`MemfdShMemProvider`] is. `GROW`, `SHRINK` and `SEAL` can be applied here.
+    /// `MemfdSeals::WRITE` cannot: this `ShmemConf` keeps a writable shared mapping of the fd for
+    /// as long as it lives, the kernel rejects a write seal while any such mapping exists
+    /// (`EBUSY`), and so this call then returns [`ShmemError::CreateFailedErr`].
+    pub fn seal(&self, seals: MemfdSeals) -> Result<(), ShmemError> {
+        if unsafe { fcntl(self.raw_fd(), F_ADD_SEALS, seals.raw()) } < 0 {
+            return Err(ShmemError::CreateFailedErr);
+        }
+        Ok(())
+    }
+
+    /// Reads back the seals currently applied to this segment via `fcntl(fd, F_GET_SEALS)`, so a
+    /// consumer can assert the segment is actually immutable before mapping it.
+    pub fn seals(&self) -> Result<MemfdSeals, ShmemError> {
+        let raw = unsafe { fcntl(self.raw_fd(), F_GET_SEALS) };
+        if raw < 0 {
+            return Err(ShmemError::CreateFailedErr);
+        }
+        Ok(MemfdSeals::from_raw(raw))
+    }
+
+    fn raw_fd(&self) -> i32 {
+        self.mem().fd()
+    }
+}
@@ -0,0 +1,148 @@
+use std::ffi::CString;
+use std::ptr::{self, NonNull};
+
+use libc::{
+    c_char, c_void, close, ftruncate, mmap, munmap, shm_open, shm_unlink, MAP_FAILED, MAP_SHARED,
+    O_CREAT, O_EXCL, O_RDWR, PROT_READ, PROT_WRITE,
+};
+
+use crate::fd::{resize_mapping, RawFdShMem, Resizable};
+use crate::provider::{NameAttachable, ShMem, ShMemProvider};
+use crate::ShmemError;
+
+/// The default [`ShMemProvider`], backed by POSIX `shm_open`/`mmap`.
+///
+/// This is the provider `Builder` uses unless a different one is selected explicitly, matching
+/// the crate's original, Unix-only behavior.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PosixShMemProvider;
+
+impl NameAttachable for PosixShMemProvider {}
+
+impl ShMemProvider for PosixShMemProvider {
+    type Mem = PosixShMem;
+
+    fn open(&self, id: &str, size: i64) -> Result<(Self::Mem, bool), ShmemError> {
+        let (fd, is_owner) = unsafe {
+            let storage_id = CString::new(id.as_bytes()).unwrap(); // Ensure proper null termination
+
+            // create the shared memory, failing with EEXIST if it already exists
+            let fd = shm_open(storage_id.as_ptr(), O_CREAT | O_EXCL | O_RDWR, 0o600);
+
+            // shared memory didn't exist
+            if fd >= 0 {
+                // allocate the shared memory with required size
+                let res = ftruncate(fd, size);
+                if res < 0 {
+                    return Err(ShmemError::AllocationFailedErr);
+                }
+
+                (fd, true)
+            } else {
+                let err = std::io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::EEXIST) {
+                    // The shared memory object already exists, so open it without O_EXCL
+                    let fd = shm_open(storage_id.as_ptr(), O_RDWR, 0o600);
+                    if fd < 0 {
+                        return Err(ShmemError::CreateFailedErr);
+                    }
+                    (fd, false)
+                } else {
+                    return Err(ShmemError::CreateFailedErr);
+                }
+            }
+        };
+
+        let null = ptr::null_mut();
+        let addr = unsafe { mmap(null, size as usize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) };
+        if addr == MAP_FAILED {
+            unsafe { close(fd) };
+            return Err(ShmemError::NullPointerErr);
+        }
+
+        Ok((
+            PosixShMem {
+                id: String::from(id),
+                fd,
+                // `MAP_FAILED` ((void*)-1) is not null, so it was just ruled out above - `addr`
+                // is a real mapping here.
+                addr: NonNull::new(addr).ok_or(ShmemError::NullPointerErr)?,
+                size,
+            },
+            is_owner,
+        ))
+    }
+
+    fn unlink(&self, id: &str) -> Result<(), ShmemError> {
+        let storage_id = CString::new(id.as_bytes()).unwrap();
+        if unsafe { shm_unlink(storage_id.as_ptr() as *const c_char) } != 0 {
+            return Err(ShmemError::CreateFailedErr);
+        }
+        Ok(())
+    }
+}
+
+/// A shared memory segment mapped via POSIX `mmap`.
+#[derive(Debug)]
+pub struct PosixShMem {
+    id: String,
+    fd: i32,
+    addr: NonNull<c_void>,
+    size: i64,
+}
+
+impl ShMem for PosixShMem {
+    fn as_ptr(&self) -> *mut u8 {
+        self.addr.as_ptr() as *mut u8
+    }
+
+    fn len(&self) -> usize {
+        self.size as usize
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl RawFdShMem for PosixShMem {
+    fn as_raw_fd(&self) -> i32 {
+        self.fd
+    }
+
+    fn from_raw_fd(fd: i32, size: i64) -> Result<Self, ShmemError> {
+        let addr = unsafe { mmap(ptr::null_mut(), size as usize, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0) };
+        if addr == MAP_FAILED {
+            return Err(ShmemError::NullPointerErr);
+        }
+
+        Ok(Self {
+            id: format!("fd:{fd}"),
+            fd,
+            addr: NonNull::new(addr).ok_or(ShmemError::NullPointerErr)?,
+            size,
+        })
+    }
+}
+
+impl Resizable for PosixShMem {
+    unsafe fn resize(&mut self, new_size: i64) -> Result<*mut u8, ShmemError> {
+        resize_mapping(self.fd, &mut self.addr, &mut self.size, new_size)?;
+        Ok(self.addr.as_ptr() as *mut u8)
+    }
+}
+
+impl Drop for PosixShMem {
+    fn drop(&mut self) {
+        // # Safety
+        //
+        // the mapping was created by `mmap` over `self.size` bytes and is unmapped exactly once.
+        if unsafe { munmap(self.addr.as_ptr(), self.size as usize) } != 0 {
+            panic!("failed to unmap shared memory from the virtual memory space")
+        }
+
+        if unsafe { close(self.fd) } != 0 {
+            panic!("failed to close shared memory file descriptor")
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::ShmemError;
+
+/// A single mapped shared memory segment.
+///
+/// Implementors own whatever platform handle (file descriptor, `HANDLE`, ...) backs the mapping
+/// and are responsible for tearing the mapping down (but not necessarily reclaiming the
+/// underlying segment - that is [`ShMemProvider::unlink`]'s job) when dropped.
+#[allow(clippy::len_without_is_empty)] // a shared memory segment is never meaningfully "empty"
+pub trait ShMem {
+    /// Pointer to the start of the mapped region.
+    fn as_ptr(&self) -> *mut u8;
+
+    /// Size in bytes of the mapped region.
+    fn len(&self) -> usize;
+
+    /// The identifier the segment was created or opened with.
+    fn id(&self) -> &str;
+}
+
+/// A backing implementation capable of creating or attaching to a named shared memory segment.
+///
+/// `shmem-bind` ships [`crate::PosixShMemProvider`] as the default, POSIX `shm_open`-based
+/// implementation. A `Win32ShMemProvider` is available on Windows, backed by
+/// `CreateFileMappingA`/`MapViewOfFile`. [`ShmemConf`](crate::ShmemConf) and
+/// [`ShmemBox`](crate::ShmemBox) are generic over this trait so the same `Builder` code compiles
+/// against either backend.
+pub trait ShMemProvider: Default {
+    /// The mapped segment type this provider produces.
+    type Mem: ShMem + std::fmt::Debug;
+
+    /// Creates or opens the segment named `id` with the given `size`, mapping it into the
+    /// process' address space.
+    ///
+    /// Returns the mapped segment along with whether this call created (and thus owns) it, in
+    /// which case the caller is responsible for eventually calling [`Self::unlink`].
+    fn open(&self, id: &str, size: i64) -> Result<(Self::Mem, bool), ShmemError>;
+
+    /// Reclaims the segment named `id` from the system.
+    ///
+    /// Called when the owning `ShmemConf` is dropped. Backends that have no notion of a
+    /// persistent, named segment outliving its mappings (such as Windows file mappings, which
+    /// are destroyed once the last handle is closed) may implement this as a no-op.
+    fn unlink(&self, id: &str) -> Result<(), ShmemError>;
+}
+
+/// Marker for [`ShMemProvider`]s whose `id` genuinely names a segment a peer can attach to, so two
+/// `open` calls for the same `id` reach the *same* underlying segment.
+///
+/// This is the precondition [`crate::Builder::ref_counted`]'s shared-counter protocol relies on:
+/// the counter is itself just another named segment (`"{id}_refcount"`), attached the same way.
+/// [`crate::MemfdShMemProvider`] doesn't implement this - every `memfd_create` produces a brand
+/// new, unrelated fd, so there is no peer to share a counter (or anything else keyed on `id`)
+/// with.
+pub trait NameAttachable: ShMemProvider {}
@@ -0,0 +1,177 @@
+use std::alloc::Layout;
+use std::mem::ManuallyDrop;
+use std::ptr;
+
+use crate::ShmemError;
+
+/// A bump allocator that serializes values directly into a fixed, already-mapped shared memory
+/// region.
+///
+/// Every [`ToShmem`] impl that owns a nested allocation (`Vec`, `String`, `Box`, ...) asks the
+/// builder for a slot via [`SharedMemoryBuilder::alloc`] instead of going through the global
+/// allocator, so the whole value tree ends up contiguous inside the segment rather than scattered
+/// across the process heap.
+pub struct SharedMemoryBuilder {
+    base: *mut u8,
+    capacity: usize,
+    offset: usize,
+}
+
+impl SharedMemoryBuilder {
+    /// # Safety
+    ///
+    /// `base` must point to at least `capacity` bytes of writable memory that stay mapped for as
+    /// long as any value produced through this builder is used.
+    pub unsafe fn new(base: *mut u8, capacity: usize) -> Self {
+        Self {
+            base,
+            capacity,
+            offset: 0,
+        }
+    }
+
+    /// The address allocations are made relative to - the same address [`ToShmem::rebase`]'s
+    /// `delta` is computed against.
+    pub fn base(&self) -> *mut u8 {
+        self.base
+    }
+
+    /// Bump-allocates `layout` within the segment, returning a pointer to the (uninitialized)
+    /// reserved region.
+    pub fn alloc(&mut self, layout: Layout) -> Result<*mut u8, ShmemError> {
+        let aligned = (self.offset + layout.align() - 1) & !(layout.align() - 1);
+        let end = aligned
+            .checked_add(layout.size())
+            .ok_or(ShmemError::AllocationFailedErr)?;
+        if end > self.capacity {
+            return Err(ShmemError::AllocationFailedErr);
+        }
+
+        self.offset = end;
+        Ok(unsafe { self.base.add(aligned) })
+    }
+}
+
+/// A type whose value can be deep-copied into a [`SharedMemoryBuilder`], relocating any of its
+/// own heap allocations into the arena so the result holds no pointers back into this process'
+/// private heap.
+///
+/// The returned value is wrapped in `ManuallyDrop` because its backing memory belongs to the
+/// shared segment rather than the global allocator: running its destructor would hand that memory
+/// to `dealloc` and corrupt the arena. It is immediately usable as a normal `Self` in the process
+/// that built it (its pointers are valid there), but a peer mapping the same segment at a
+/// different base address must first call [`ToShmem::rebase`] on its own copy of the bytes.
+///
+/// There is no derive macro for `ToShmem` - implementing it for a user-defined struct means
+/// writing `to_shmem`/`rebase` by hand, field by field, the same way the impls in this module do
+/// for `String`/`Vec`/`Box`. That's a deliberate gap, not an oversight we haven't gotten to yet.
+pub trait ToShmem: Sized {
+    /// Serializes `self` into `builder`.
+    fn to_shmem(&self, builder: &mut SharedMemoryBuilder)
+        -> Result<ManuallyDrop<Self>, ShmemError>;
+
+    /// Shifts every pointer a prior [`Self::to_shmem`] call wrote into `value` by `delta` bytes,
+    /// so a value built against one mapping of the arena becomes valid against another.
+    ///
+    /// # Safety
+    ///
+    /// `value` must be exactly what `to_shmem` produced (or a byte-for-byte copy of it), still
+    /// describing pointers into a mapping of the same arena at some `old_base`, and `delta` must
+    /// equal `new_base as isize - old_base as isize` for the mapping `value` should describe
+    /// after this call.
+    unsafe fn rebase(_value: &mut Self, _delta: isize) {}
+}
+
+macro_rules! impl_to_shmem_pod {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl ToShmem for $t {
+                fn to_shmem(
+                    &self,
+                    _builder: &mut SharedMemoryBuilder,
+                ) -> Result<ManuallyDrop<Self>, ShmemError> {
+                    // plain data, no inner pointers to relocate or rebase.
+                    Ok(ManuallyDrop::new(*self))
+                }
+            }
+        )*
+    };
+}
+impl_to_shmem_pod!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64, bool, char);
+
+impl ToShmem for String {
+    fn to_shmem(&self, builder: &mut SharedMemoryBuilder) -> Result<ManuallyDrop<Self>, ShmemError> {
+        let bytes = self.as_bytes();
+        let layout = Layout::array::<u8>(bytes.len()).map_err(|_| ShmemError::AllocationFailedErr)?;
+        let dst = builder.alloc(layout)?;
+        unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), dst, bytes.len()) };
+
+        // # Safety
+        //
+        // `dst` holds exactly `bytes.len()` bytes just copied from a valid `&str`, so it is
+        // valid utf-8 of that length and capacity.
+        let relocated = unsafe { String::from_raw_parts(dst, bytes.len(), bytes.len()) };
+        Ok(ManuallyDrop::new(relocated))
+    }
+
+    unsafe fn rebase(value: &mut Self, delta: isize) {
+        let len = value.len();
+        let old_ptr = value.as_ptr();
+        let rebased_ptr = old_ptr.wrapping_byte_offset(delta) as *mut u8;
+
+        // overwrites `value` without running its (invalid, foreign-pointer) destructor.
+        ptr::write(value, String::from_raw_parts(rebased_ptr, len, len));
+    }
+}
+
+impl<T: ToShmem> ToShmem for Vec<T> {
+    fn to_shmem(&self, builder: &mut SharedMemoryBuilder) -> Result<ManuallyDrop<Self>, ShmemError> {
+        let layout = Layout::array::<T>(self.len()).map_err(|_| ShmemError::AllocationFailedErr)?;
+        let dst = builder.alloc(layout)?.cast::<T>();
+
+        for (i, item) in self.iter().enumerate() {
+            let relocated = item.to_shmem(builder)?;
+            unsafe { ptr::write(dst.add(i), ManuallyDrop::into_inner(relocated)) };
+        }
+
+        // # Safety
+        //
+        // `dst` holds exactly `self.len()` initialized `T`s just written above.
+        let relocated = unsafe { Vec::from_raw_parts(dst, self.len(), self.len()) };
+        Ok(ManuallyDrop::new(relocated))
+    }
+
+    unsafe fn rebase(value: &mut Self, delta: isize) {
+        let len = value.len();
+        let old_ptr = value.as_ptr();
+        let rebased_ptr = old_ptr.wrapping_byte_offset(delta) as *mut T;
+
+        let mut rebased = Vec::from_raw_parts(rebased_ptr, len, len);
+        for item in rebased.iter_mut() {
+            T::rebase(item, delta);
+        }
+        ptr::write(value, rebased);
+    }
+}
+
+impl<T: ToShmem> ToShmem for Box<T> {
+    fn to_shmem(&self, builder: &mut SharedMemoryBuilder) -> Result<ManuallyDrop<Self>, ShmemError> {
+        let relocated = (**self).to_shmem(builder)?;
+        let dst = builder.alloc(Layout::new::<T>())?.cast::<T>();
+        unsafe { ptr::write(dst, ManuallyDrop::into_inner(relocated)) };
+
+        // # Safety
+        //
+        // `dst` was just initialized above and came from the arena, which outlives this `Box`.
+        Ok(ManuallyDrop::new(unsafe { Box::from_raw(dst) }))
+    }
+
+    unsafe fn rebase(value: &mut Self, delta: isize) {
+        let old_ptr = Box::into_raw(ptr::read(value));
+        let rebased_ptr = old_ptr.wrapping_byte_offset(delta);
+
+        let mut rebased = Box::from_raw(rebased_ptr);
+        T::rebase(&mut rebased, delta);
+        ptr::write(value, rebased);
+    }
+}
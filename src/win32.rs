@@ -0,0 +1,113 @@
+use std::ffi::CString;
+use std::ptr::NonNull;
+
+use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, HANDLE, INVALID_HANDLE_VALUE};
+use windows_sys::Win32::System::Memory::{
+    CreateFileMappingA, MapViewOfFile, UnmapViewOfFile, FILE_MAP_ALL_ACCESS,
+    MEMORY_MAPPED_VIEW_ADDRESS, PAGE_READWRITE,
+};
+
+use crate::provider::{NameAttachable, ShMem, ShMemProvider};
+use crate::ShmemError;
+
+/// A [`ShMemProvider`] backed by Windows named file mappings, via
+/// `CreateFileMappingA`/`MapViewOfFile`.
+///
+/// Segments are backed by the system paging file (`INVALID_HANDLE_VALUE`), so `id` plays the
+/// same role as the `flink_id` passed to `shm_open` on the POSIX side.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Win32ShMemProvider;
+
+impl NameAttachable for Win32ShMemProvider {}
+
+impl ShMemProvider for Win32ShMemProvider {
+    type Mem = Win32ShMem;
+
+    fn open(&self, id: &str, size: i64) -> Result<(Self::Mem, bool), ShmemError> {
+        let storage_id = CString::new(id.as_bytes()).unwrap();
+
+        let handle = unsafe {
+            CreateFileMappingA(
+                INVALID_HANDLE_VALUE,
+                std::ptr::null(),
+                PAGE_READWRITE,
+                ((size as u64) >> 32) as u32,
+                (size as u64) as u32,
+                storage_id.as_ptr() as *const u8,
+            )
+        };
+
+        if handle == 0 {
+            return Err(ShmemError::CreateFailedErr);
+        }
+
+        // `CreateFileMappingA` creates the mapping if it doesn't exist yet, and otherwise opens a
+        // handle to the existing one, setting `ERROR_ALREADY_EXISTS` to tell the two cases apart.
+        let is_owner = unsafe { GetLastError() } != windows_sys::Win32::Foundation::ERROR_ALREADY_EXISTS;
+
+        let addr = unsafe { MapViewOfFile(handle, FILE_MAP_ALL_ACCESS, 0, 0, size as usize) };
+
+        let addr = match NonNull::new(addr.Value) {
+            Some(addr) => addr,
+            None => {
+                unsafe { CloseHandle(handle) };
+                return Err(ShmemError::NullPointerErr);
+            }
+        };
+
+        Ok((
+            Win32ShMem {
+                id: String::from(id),
+                handle,
+                addr,
+                size,
+            },
+            is_owner,
+        ))
+    }
+
+    fn unlink(&self, _id: &str) -> Result<(), ShmemError> {
+        // Windows file mappings have no persistent, named existence of their own: the backing
+        // section is destroyed once the last handle to it (across every process) is closed, so
+        // there is nothing to unlink explicitly.
+        Ok(())
+    }
+}
+
+/// A shared memory segment mapped via `MapViewOfFile`.
+#[derive(Debug)]
+pub struct Win32ShMem {
+    id: String,
+    handle: HANDLE,
+    addr: NonNull<std::ffi::c_void>,
+    size: i64,
+}
+
+impl ShMem for Win32ShMem {
+    fn as_ptr(&self) -> *mut u8 {
+        self.addr.as_ptr() as *mut u8
+    }
+
+    fn len(&self) -> usize {
+        self.size as usize
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Drop for Win32ShMem {
+    fn drop(&mut self) {
+        let view = MEMORY_MAPPED_VIEW_ADDRESS {
+            Value: self.addr.as_ptr(),
+        };
+        if unsafe { UnmapViewOfFile(view) } == 0 {
+            panic!("failed to unmap shared memory from the virtual memory space")
+        }
+
+        if unsafe { CloseHandle(self.handle) } == 0 {
+            panic!("failed to close shared memory mapping handle")
+        }
+    }
+}